@@ -1,16 +1,24 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::Infallible;
 use std::future::Future;
 use std::pin::Pin;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use async_trait::async_trait;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
 use futures::{Stream, StreamExt};
+use hdrhistogram::Histogram;
 use rand::Rng;
-use sqlx::{Pool, Sqlite};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
+use sqlx::{Pool, Postgres, Sqlite};
 use starknet::core::types::Felt;
-use tokio::sync::mpsc::{channel, Receiver, Sender};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, Notify, RwLock};
 use torii_core::error::{Error, ParseError};
 use torii_core::simple_broker::SimpleBroker;
 use torii_core::types::ContractCursor as ContractUpdated;
@@ -21,56 +29,571 @@ use crate::proto::world::SubscribeIndexerResponse;
 
 pub(crate) const LOG_TARGET: &str = "torii::grpc::server::subscriptions::indexer";
 
+/// Number of recent [`ContractUpdated`] events retained per contract so that a
+/// reconnecting subscriber can be replayed the progress it missed while it was
+/// disconnected.
+const REPLAY_BUFFER_SIZE: usize = 128;
+
+/// Default window after which the per-contract latency histograms are reset so
+/// that percentiles track recent indexing health rather than all-time history.
+const DEFAULT_HISTOGRAM_WINDOW: Duration = Duration::from_secs(60);
+
+/// Per-contract recorder that tracks the distribution of inter-update intervals
+/// and per-block processing delays, exposing tps percentiles and an estimate of
+/// how many blocks the indexer is behind wall-clock time.
 #[derive(Debug)]
-pub struct IndexerSubscriber {
-    /// Contract address that the subscriber is interested in
-    contract_address: Felt,
-    /// The channel to send the response back to the subscriber.
-    sender: Sender<Result<proto::world::SubscribeIndexerResponse, tonic::Status>>,
+struct LatencyRecorder {
+    /// Inter-update intervals, in milliseconds.
+    intervals: Histogram<u64>,
+    /// Per-update advancement of `last_block_timestamp`, in seconds.
+    block_deltas: Histogram<u64>,
+    last_update_at: Option<Instant>,
+    last_block_timestamp: i64,
+    window_start: Instant,
+    window: Duration,
+}
+
+impl LatencyRecorder {
+    fn new(window: Duration) -> Self {
+        Self {
+            intervals: Histogram::new(3).expect("valid significant figures"),
+            block_deltas: Histogram::new(3).expect("valid significant figures"),
+            last_update_at: None,
+            last_block_timestamp: 0,
+            window_start: Instant::now(),
+            window,
+        }
+    }
+
+    fn record(&mut self, update: &ContractUpdated, now: Instant) {
+        if now.duration_since(self.window_start) >= self.window {
+            self.intervals.reset();
+            self.block_deltas.reset();
+            self.window_start = now;
+            self.last_update_at = None;
+        }
+
+        if let Some(prev) = self.last_update_at {
+            let interval_ms = now.duration_since(prev).as_millis() as u64;
+            let _ = self.intervals.record(interval_ms.max(1));
+        }
+        self.last_update_at = Some(now);
+
+        if self.last_block_timestamp != 0 {
+            let delta = (update.last_block_timestamp - self.last_block_timestamp).max(0) as u64;
+            let _ = self.block_deltas.record(delta);
+        }
+        self.last_block_timestamp = update.last_block_timestamp;
+    }
+
+    /// Throughput at the given quantile of the interval distribution. The
+    /// quantile is taken over inter-update intervals, so higher quantiles are
+    /// the *slow* tail: `tps_at_quantile(0.95)` is the throughput during the
+    /// 95th-percentile-longest gap between updates. This is deliberate — it
+    /// surfaces the stalls and catch-up bursts operators care about, which a
+    /// high-throughput percentile would hide. `p50` is the median throughput.
+    fn tps_at_quantile(&self, quantile: f64) -> f64 {
+        let interval_ms = self.intervals.value_at_quantile(quantile);
+        if interval_ms == 0 {
+            0.0
+        } else {
+            1000.0 / interval_ms as f64
+        }
+    }
+
+    /// Estimated number of blocks the indexer is behind wall-clock time, derived
+    /// from the median per-block processing delta.
+    fn blocks_behind(&self, update: &ContractUpdated) -> i64 {
+        let median_delta = self.block_deltas.value_at_quantile(0.5);
+        if median_delta == 0 {
+            return 0;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(update.last_block_timestamp);
+        (now - update.last_block_timestamp).max(0) / median_delta as i64
+    }
+}
+
+/// Snapshot of the latency percentiles shipped alongside each update.
+#[derive(Debug, Default, Clone, Copy)]
+struct LatencySnapshot {
+    tps_p50: f64,
+    tps_p95: f64,
+    tps_p99: f64,
+    blocks_behind: i64,
+}
+
+/// Default Postgres channel the indexer `NOTIFY`s cursor advances on.
+pub const DEFAULT_NOTIFY_CHANNEL: &str = "torii_contract_updated";
+
+/// Storage backend abstraction for the indexer subscription server. This
+/// decouples the gRPC server from the process that performs the indexing: a
+/// backend exposes both the current cursor snapshot and a stream of live
+/// updates, so multiple torii-grpc instances can share one database.
+#[async_trait]
+pub trait IndexerBackend: Send + Sync + std::fmt::Debug {
+    /// Loads the current [`ContractUpdated`] rows, optionally restricted to the
+    /// given set of contract addresses (an empty set loads all contracts).
+    async fn contracts(
+        &self,
+        contract_addresses: &HashSet<Felt>,
+    ) -> Result<Vec<ContractUpdated>, Error>;
+
+    /// Returns a stream of live cursor advances feeding [`Service`].
+    fn subscribe(&self) -> Pin<Box<dyn Stream<Item = ContractUpdated> + Send>>;
+}
+
+/// Columns selected by every backend's `contracts` query, in the order
+/// [`ContractUpdated`] expects them.
+const CONTRACTS_SELECT: &str =
+    "SELECT head, tps, last_block_timestamp, contract_address FROM contracts";
+
+/// Builds the `contracts` statement, appending a `WHERE id IN (...)` clause with
+/// `count` placeholders rendered by `placeholder` (1-indexed). An empty set
+/// yields the bare wildcard SELECT. This keeps the two backends from drifting;
+/// they differ only in placeholder syntax (`?` for SQLite, `$i` for Postgres).
+fn contracts_statement(count: usize, placeholder: impl Fn(usize) -> String) -> String {
+    if count == 0 {
+        return CONTRACTS_SELECT.to_string();
+    }
+    let placeholders = (1..=count).map(placeholder).collect::<Vec<_>>().join(", ");
+    format!("{CONTRACTS_SELECT} WHERE id IN ({placeholders})")
 }
 
+/// In-process backend reading from SQLite and receiving live updates over the
+/// [`SimpleBroker`]; this is the default single-process deployment.
+#[derive(Debug, Clone)]
+pub struct SqliteBackend {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteBackend {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IndexerBackend for SqliteBackend {
+    async fn contracts(
+        &self,
+        contract_addresses: &HashSet<Felt>,
+    ) -> Result<Vec<ContractUpdated>, Error> {
+        let statement = contracts_statement(contract_addresses.len(), |_| "?".to_string());
+
+        let mut query = sqlx::query_as(&statement);
+        for address in contract_addresses {
+            query = query.bind(format!("{address:#x}"));
+        }
+        Ok(query.fetch_all(&self.pool).await?)
+    }
+
+    fn subscribe(&self) -> Pin<Box<dyn Stream<Item = ContractUpdated> + Send>> {
+        Box::pin(SimpleBroker::<ContractUpdated>::subscribe())
+    }
+}
+
+/// JSON payload `NOTIFY`d by the indexer on each cursor advance.
+#[derive(Debug, Deserialize)]
+struct NotifyPayload {
+    contract_address: String,
+    head: i64,
+    tps: i64,
+    last_block_timestamp: i64,
+}
+
+/// Backend reading from Postgres and receiving live updates over a `LISTEN`
+/// channel, allowing the gRPC server to run decoupled from the indexer and to
+/// scale read fan-out across processes.
+#[derive(Debug, Clone)]
+pub struct PostgresBackend {
+    pool: Pool<Postgres>,
+    channel: String,
+}
+
+impl PostgresBackend {
+    pub fn new(pool: Pool<Postgres>, channel: impl Into<String>) -> Self {
+        Self { pool, channel: channel.into() }
+    }
+}
+
+#[async_trait]
+impl IndexerBackend for PostgresBackend {
+    async fn contracts(
+        &self,
+        contract_addresses: &HashSet<Felt>,
+    ) -> Result<Vec<ContractUpdated>, Error> {
+        let statement = contracts_statement(contract_addresses.len(), |i| format!("${i}"));
+
+        let mut query = sqlx::query_as(&statement);
+        for address in contract_addresses {
+            query = query.bind(format!("{address:#x}"));
+        }
+        Ok(query.fetch_all(&self.pool).await?)
+    }
+
+    fn subscribe(&self) -> Pin<Box<dyn Stream<Item = ContractUpdated> + Send>> {
+        let pool = self.pool.clone();
+        let channel = self.channel.clone();
+        // Turn the `LISTEN` notification stream into the same `ContractUpdated`
+        // items the in-process broker produces.
+        let stream = async_stream::stream! {
+            let mut listener = match PgListener::connect_with(&pool).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!(target = LOG_TARGET, error = %e, "Connecting Postgres listener.");
+                    return;
+                }
+            };
+            if let Err(e) = listener.listen(&channel).await {
+                error!(target = LOG_TARGET, error = %e, "Listening on Postgres channel.");
+                return;
+            }
+
+            loop {
+                match listener.recv().await {
+                    Ok(notification) => {
+                        match serde_json::from_str::<NotifyPayload>(notification.payload()) {
+                            Ok(payload) => yield ContractUpdated {
+                                head: payload.head,
+                                tps: payload.tps,
+                                last_block_timestamp: payload.last_block_timestamp,
+                                contract_address: payload.contract_address,
+                            },
+                            Err(e) => error!(
+                                target = LOG_TARGET,
+                                error = %e,
+                                "Decoding Postgres notify payload."
+                            ),
+                        }
+                    }
+                    Err(e) => {
+                        error!(target = LOG_TARGET, error = %e, "Receiving Postgres notification.");
+                        return;
+                    }
+                }
+            }
+        };
+        Box::pin(stream)
+    }
+}
+
+type IndexerResult = Result<proto::world::SubscribeIndexerResponse, tonic::Status>;
+
+#[derive(Debug, Default)]
+struct ChannelState {
+    /// Ordered messages (initial snapshot + replayed gap) delivered verbatim.
+    backlog: VecDeque<IndexerResult>,
+    /// Latest live update per contract, coalesced while the consumer lags.
+    latest: HashMap<Felt, IndexerResult>,
+    /// Order in which contracts first produced a pending live update since the
+    /// last drain, so coalesced values drain fairly; overwriting a pending
+    /// value does not move its contract.
+    order: VecDeque<Felt>,
+}
+
+/// Lossy-but-latest delivery slot for a single subscriber. Ordered snapshot and
+/// replay messages are queued verbatim, while live updates for a contract are
+/// overwritten in place so a slow consumer only ever falls behind by one value
+/// per contract instead of stalling the publisher or dropping the connection.
 #[derive(Debug, Default)]
+struct SubscriberChannel {
+    state: Mutex<ChannelState>,
+    notify: Notify,
+}
+
+impl SubscriberChannel {
+    /// Queues an ordered message (initial snapshot or replay) for verbatim
+    /// delivery.
+    async fn push_ordered(&self, item: IndexerResult) {
+        self.state.lock().await.backlog.push_back(item);
+        self.notify.notify_one();
+    }
+
+    /// Coalesces a live value for `contract` into the latest slot. Publish tasks
+    /// run unordered (one `tokio::spawn` per event), so a stale head must never
+    /// clobber a newer coalesced value: an existing success is only overwritten
+    /// when the incoming head is at least as high. Errors always take over so a
+    /// failure is surfaced rather than swallowed.
+    async fn push_latest(&self, contract: Felt, item: IndexerResult) {
+        let mut state = self.state.lock().await;
+        match state.latest.get(&contract) {
+            Some(Ok(existing)) => {
+                let overwrite = match &item {
+                    Ok(incoming) => incoming.head >= existing.head,
+                    Err(_) => true,
+                };
+                if overwrite {
+                    state.latest.insert(contract, item);
+                }
+            }
+            Some(Err(_)) => {
+                // Keep a pending error until it is drained; only a newer error
+                // replaces it.
+                if item.is_err() {
+                    state.latest.insert(contract, item);
+                }
+            }
+            None => {
+                state.latest.insert(contract, item);
+                state.order.push_back(contract);
+            }
+        }
+        self.notify.notify_one();
+    }
+
+    /// Drains all currently pending values: ordered backlog first, then the
+    /// coalesced per-contract live values in arrival order.
+    async fn drain(&self) -> Vec<IndexerResult> {
+        let mut state = self.state.lock().await;
+        let mut out: Vec<IndexerResult> = state.backlog.drain(..).collect();
+        while let Some(contract) = state.order.pop_front() {
+            if let Some(item) = state.latest.remove(&contract) {
+                out.push(item);
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug)]
+pub struct IndexerSubscriber {
+    /// Contract addresses the subscriber is interested in. An empty set acts as
+    /// a wildcard and forwards updates for every contract.
+    contract_addresses: HashSet<Felt>,
+    /// The lossy-but-latest delivery slot shared with the consumer stream.
+    channel: Arc<SubscriberChannel>,
+}
+
+impl IndexerSubscriber {
+    /// Whether the consumer stream has been dropped. The manager holds one
+    /// reference and each live stream holds another, so a strong count of one
+    /// means the receiver is gone and the subscriber can be evicted.
+    fn is_closed(&self) -> bool {
+        Arc::strong_count(&self.channel) == 1
+    }
+}
+
+/// Removes a subscriber from its manager as soon as the consumer stream is
+/// dropped, so disconnected clients don't leak in the `subscribers` map until
+/// the next matching publish. Moved into the consumer stream, it is dropped with
+/// it; `publish_updates`' [`IndexerSubscriber::is_closed`] check remains a
+/// belt-and-braces fallback.
+#[derive(Debug)]
+struct SubscriberGuard {
+    manager: Arc<IndexerManager>,
+    id: usize,
+}
+
+impl Drop for SubscriberGuard {
+    fn drop(&mut self) {
+        let manager = Arc::clone(&self.manager);
+        let id = self.id;
+        tokio::spawn(async move { manager.remove_subscriber(id).await });
+    }
+}
+
+#[derive(Debug)]
 pub struct IndexerManager {
     subscribers: RwLock<HashMap<usize, IndexerSubscriber>>,
+    /// Bounded ring buffer of the most recent updates per contract address,
+    /// used to replay the gap to reconnecting subscribers. Recorded from the
+    /// ordered poll loop, so a plain mutex keeps the per-contract order intact.
+    replay_buffer: StdMutex<HashMap<Felt, VecDeque<ContractUpdated>>>,
+    /// Per-contract latency/throughput recorders backing the tps percentiles.
+    latency: RwLock<HashMap<Felt, LatencyRecorder>>,
+    /// Window after which each [`LatencyRecorder`] is rotated.
+    histogram_window: Duration,
+}
+
+impl Default for IndexerManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTOGRAM_WINDOW)
+    }
 }
 
 impl IndexerManager {
+    pub fn new(histogram_window: Duration) -> Self {
+        Self {
+            subscribers: RwLock::new(HashMap::new()),
+            replay_buffer: StdMutex::new(HashMap::new()),
+            latency: RwLock::new(HashMap::new()),
+            histogram_window,
+        }
+    }
+
     pub async fn add_subscriber(
-        &self,
-        pool: &Pool<Sqlite>,
-        contract_address: Felt,
-    ) -> Result<Receiver<Result<proto::world::SubscribeIndexerResponse, tonic::Status>>, Error>
-    {
+        self: &Arc<Self>,
+        backend: &dyn IndexerBackend,
+        contract_addresses: Vec<Felt>,
+        from_head: Option<i64>,
+    ) -> Result<impl Stream<Item = IndexerResult>, Error> {
         let id = rand::thread_rng().gen::<usize>();
-        let (sender, receiver) = channel(1);
+        let channel = Arc::new(SubscriberChannel::default());
 
-        let mut statement =
-            "SELECT head, tps, last_block_timestamp, contract_address FROM contracts".to_string();
+        // An empty set (or the explicit `Felt::ZERO` wildcard) means the
+        // subscriber wants every contract.
+        let contract_addresses: HashSet<Felt> =
+            contract_addresses.into_iter().filter(|address| *address != Felt::ZERO).collect();
 
-        let contracts: Vec<ContractUpdated> = if contract_address != Felt::ZERO {
-            statement += " WHERE id = ?";
-
-            sqlx::query_as(&statement)
-                .bind(format!("{:#x}", contract_address))
-                .fetch_all(pool)
-                .await?
-        } else {
-            sqlx::query_as(&statement).fetch_all(pool).await?
-        };
+        let contracts = backend.contracts(&contract_addresses).await?;
 
         for contract in contracts {
-            let _ = sender
-                .send(Ok(SubscribeIndexerResponse {
+            let contract_address =
+                Felt::from_str(&contract.contract_address).map_err(ParseError::FromStr)?;
+
+            // If the client is reconnecting, try to replay the progress it missed
+            // from the ring buffer before handing it the current snapshot.
+            let gap_truncated = if let Some(from_head) = from_head {
+                self.replay_gap(&channel, &contract, from_head).await
+            } else {
+                false
+            };
+
+            let latency = self.latency_snapshot(&contract).await;
+            channel
+                .push_ordered(Ok(SubscribeIndexerResponse {
                     head: contract.head,
                     tps: contract.tps,
                     last_block_timestamp: contract.last_block_timestamp,
                     contract_address: contract_address.to_bytes_be().to_vec(),
+                    gap_truncated,
+                    tps_p50: latency.tps_p50,
+                    tps_p95: latency.tps_p95,
+                    tps_p99: latency.tps_p99,
+                    blocks_behind: latency.blocks_behind,
+                }))
+                .await;
+        }
+        self.subscribers
+            .write()
+            .await
+            .insert(id, IndexerSubscriber { contract_addresses, channel: Arc::clone(&channel) });
+
+        // The consumer drains the lossy-but-latest slot whenever it is notified.
+        // The guard, moved into the stream, removes the subscriber from the
+        // manager the moment this stream is dropped.
+        let guard = SubscriberGuard { manager: Arc::clone(self), id };
+        Ok(async_stream::stream! {
+            let _guard = guard;
+            loop {
+                for item in channel.drain().await {
+                    yield item;
+                }
+                channel.notify.notified().await;
+            }
+        })
+    }
+
+    /// Replays the buffered updates in the open range `(from_head,
+    /// contract.head)` — strictly newer than what the client last saw and
+    /// strictly older than the current head, which `add_subscriber` pushes as
+    /// the snapshot afterwards, so the head is never delivered twice. Returns
+    /// `true` when head `from_head + 1` is older than the oldest buffered entry:
+    /// the gap can't be replayed contiguously, so nothing is replayed and the
+    /// client falls back to the current snapshot (with the flag set) for a full
+    /// resync.
+    async fn replay_gap(
+        &self,
+        channel: &SubscriberChannel,
+        contract: &ContractUpdated,
+        from_head: i64,
+    ) -> bool {
+        // Collect the entries to replay while holding the lock, then release it
+        // before awaiting on the channel.
+        let (gap_truncated, replayed) = {
+            let buffer = self.replay_buffer.lock().unwrap();
+            let Some(entries) = buffer.get(&Self::contract_key(&contract.contract_address)) else {
+                // Nothing buffered for this contract; if it already advanced past
+                // the client we cannot prove continuity, so flag a truncated gap.
+                return contract.head > from_head;
+            };
+
+            let gap_truncated =
+                entries.front().map(|oldest| oldest.head > from_head + 1).unwrap_or(false);
+            // When the gap is truncated the sequence would be non-contiguous and
+            // the client is told to discard it anyway, so skip the partial replay
+            // and let the snapshot alone bring it up to head.
+            let replayed: Vec<ContractUpdated> = if gap_truncated {
+                Vec::new()
+            } else {
+                entries
+                    .iter()
+                    .filter(|entry| entry.head > from_head && entry.head < contract.head)
+                    .cloned()
+                    .collect()
+            };
+            (gap_truncated, replayed)
+        };
+
+        for entry in replayed {
+            let contract_address = Felt::from_str(&entry.contract_address).unwrap_or(Felt::ZERO);
+            channel
+                .push_ordered(Ok(SubscribeIndexerResponse {
+                    head: entry.head,
+                    tps: entry.tps,
+                    last_block_timestamp: entry.last_block_timestamp,
+                    contract_address: contract_address.to_bytes_be().to_vec(),
+                    gap_truncated,
+                    tps_p50: 0.0,
+                    tps_p95: 0.0,
+                    tps_p99: 0.0,
+                    blocks_behind: 0,
                 }))
                 .await;
         }
-        self.subscribers.write().await.insert(id, IndexerSubscriber { contract_address, sender });
 
-        Ok(receiver)
+        gap_truncated
+    }
+
+    /// Records an update into the per-contract ring buffer, evicting the oldest
+    /// entry once [`REPLAY_BUFFER_SIZE`] is reached.
+    fn record_update(&self, update: &ContractUpdated) {
+        let key = Self::contract_key(&update.contract_address);
+        let mut buffer = self.replay_buffer.lock().unwrap();
+        let entries = buffer.entry(key).or_default();
+        if entries.len() == REPLAY_BUFFER_SIZE {
+            entries.pop_front();
+        }
+        entries.push_back(update.clone());
+    }
+
+    /// Records an update into the per-contract latency histograms and returns
+    /// the resulting percentile snapshot.
+    async fn record_latency(&self, update: &ContractUpdated) -> LatencySnapshot {
+        let key = Self::contract_key(&update.contract_address);
+        let window = self.histogram_window;
+        let mut latency = self.latency.write().await;
+        let recorder = latency.entry(key).or_insert_with(|| LatencyRecorder::new(window));
+        recorder.record(update, Instant::now());
+        LatencySnapshot {
+            tps_p50: recorder.tps_at_quantile(0.50),
+            tps_p95: recorder.tps_at_quantile(0.95),
+            tps_p99: recorder.tps_at_quantile(0.99),
+            blocks_behind: recorder.blocks_behind(update),
+        }
+    }
+
+    /// Reads the current percentile snapshot for a contract without recording a
+    /// new sample, used when emitting the initial snapshot.
+    async fn latency_snapshot(&self, update: &ContractUpdated) -> LatencySnapshot {
+        let key = Self::contract_key(&update.contract_address);
+        let latency = self.latency.read().await;
+        match latency.get(&key) {
+            Some(recorder) => LatencySnapshot {
+                tps_p50: recorder.tps_at_quantile(0.50),
+                tps_p95: recorder.tps_at_quantile(0.95),
+                tps_p99: recorder.tps_at_quantile(0.99),
+                blocks_behind: recorder.blocks_behind(update),
+            },
+            None => LatencySnapshot::default(),
+        }
+    }
+
+    fn contract_key(contract_address: &str) -> Felt {
+        Felt::from_str(contract_address).unwrap_or(Felt::ZERO)
     }
 
     pub(super) async fn remove_subscriber(&self, id: usize) {
@@ -82,12 +605,12 @@ impl IndexerManager {
 #[allow(missing_debug_implementations)]
 pub struct Service {
     subs_manager: Arc<IndexerManager>,
-    simple_broker: Pin<Box<dyn Stream<Item = ContractUpdated> + Send>>,
+    updates: Pin<Box<dyn Stream<Item = ContractUpdated> + Send>>,
 }
 
 impl Service {
-    pub fn new(subs_manager: Arc<IndexerManager>) -> Self {
-        Self { subs_manager, simple_broker: Box::pin(SimpleBroker::<ContractUpdated>::subscribe()) }
+    pub fn new(subs_manager: Arc<IndexerManager>, backend: &dyn IndexerBackend) -> Self {
+        Self { subs_manager, updates: backend.subscribe() }
     }
 
     async fn publish_updates(
@@ -98,8 +621,20 @@ impl Service {
         let contract_address =
             Felt::from_str(&update.contract_address).map_err(ParseError::FromStr)?;
 
+        let latency = subs.record_latency(update).await;
+
         for (idx, sub) in subs.subscribers.read().await.iter() {
-            if sub.contract_address != Felt::ZERO && sub.contract_address != contract_address {
+            if !sub.contract_addresses.is_empty()
+                && !sub.contract_addresses.contains(&contract_address)
+            {
+                continue;
+            }
+
+            // Evict only when the consumer is actually gone, never merely
+            // because it is lagging: a slow subscriber has its pending value for
+            // this contract coalesced into the newest one.
+            if sub.is_closed() {
+                closed_stream.push(*idx);
                 continue;
             }
 
@@ -108,11 +643,14 @@ impl Service {
                 tps: update.tps,
                 last_block_timestamp: update.last_block_timestamp,
                 contract_address: contract_address.to_bytes_be().to_vec(),
+                gap_truncated: false,
+                tps_p50: latency.tps_p50,
+                tps_p95: latency.tps_p95,
+                tps_p99: latency.tps_p99,
+                blocks_behind: latency.blocks_behind,
             };
 
-            if sub.sender.send(Ok(resp)).await.is_err() {
-                closed_stream.push(*idx);
-            }
+            sub.channel.push_latest(contract_address, Ok(resp)).await;
         }
 
         for id in closed_stream {
@@ -130,7 +668,12 @@ impl Future for Service {
     fn poll(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> std::task::Poll<Self::Output> {
         let pin = self.get_mut();
 
-        while let Poll::Ready(Some(event)) = pin.simple_broker.poll_next_unpin(cx) {
+        while let Poll::Ready(Some(event)) = pin.updates.poll_next_unpin(cx) {
+            // Record into the ring buffer here, in the ordered poll loop, so the
+            // per-contract history stays head-ascending regardless of the order
+            // in which the spawned publish tasks run.
+            pin.subs_manager.record_update(&event);
+
             let subs = Arc::clone(&pin.subs_manager);
             tokio::spawn(async move {
                 if let Err(e) = Service::publish_updates(subs, &event).await {
@@ -141,4 +684,215 @@ impl Future for Service {
 
         Poll::Pending
     }
-}
\ No newline at end of file
+}
+
+/// Shared state for the Server-Sent Events gateway: the [`IndexerManager`] that
+/// owns the subscribers and the [`IndexerBackend`] used to seed them.
+#[derive(Clone)]
+#[allow(missing_debug_implementations)]
+pub struct IndexerSseState {
+    pub manager: Arc<IndexerManager>,
+    pub backend: Arc<dyn IndexerBackend>,
+}
+
+/// Query parameters for the SSE endpoint.
+#[derive(Debug, Deserialize)]
+pub struct IndexerSseQuery {
+    /// Optional contract address to restrict the stream to; omitted for the
+    /// wildcard firehose.
+    contract_address: Option<String>,
+}
+
+/// The JSON body of each `text/event-stream` event, mirroring the fields of
+/// [`SubscribeIndexerResponse`] that browser dashboards care about.
+#[derive(Debug, Serialize)]
+struct IndexerSseEvent {
+    head: i64,
+    tps: i64,
+    last_block_timestamp: i64,
+    contract_address: String,
+}
+
+/// SSE gateway mirroring the indexer gRPC stream for consumers that can't speak
+/// gRPC/tonic. It reuses [`IndexerManager::add_subscriber`], so the
+/// initial-snapshot-then-live-updates semantics match the gRPC path, and the
+/// subscriber is removed from the manager as soon as the HTTP body stream is
+/// dropped.
+pub async fn indexer_sse_handler(
+    State(state): State<IndexerSseState>,
+    Query(query): Query<IndexerSseQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let contract_addresses = match query.contract_address {
+        Some(address) => vec![Felt::from_str(&address)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?],
+        None => vec![],
+    };
+
+    let updates = state
+        .manager
+        .add_subscriber(state.backend.as_ref(), contract_addresses, None)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let stream = updates.filter_map(|item| async move {
+        let resp = item.ok()?;
+        let contract_address = Felt::from_bytes_be_slice(&resp.contract_address);
+        let payload = IndexerSseEvent {
+            head: resp.head,
+            tps: resp.tps,
+            last_block_timestamp: resp.last_block_timestamp,
+            contract_address: format!("{contract_address:#x}"),
+        };
+        Some(Ok(Event::default().json_data(payload).unwrap_or_default()))
+    });
+
+    // Periodic keep-alive comments stop idle connections and proxies from timing
+    // the stream out.
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn contract_updated(head: i64, contract_address: &str) -> ContractUpdated {
+        ContractUpdated {
+            head,
+            tps: 0,
+            last_block_timestamp: 0,
+            contract_address: contract_address.to_string(),
+        }
+    }
+
+    fn heads(items: Vec<IndexerResult>) -> Vec<i64> {
+        items.into_iter().map(|item| item.unwrap().head).collect()
+    }
+
+    #[tokio::test]
+    async fn replay_gap_is_bounded_by_client_and_current_head() {
+        let manager = IndexerManager::default();
+        let address = "0x1";
+        for head in 1..=3 {
+            manager.record_update(&contract_updated(head, address));
+        }
+        let current = contract_updated(3, address);
+
+        // `from_head` right before the oldest buffered entry, so the gap is
+        // contiguous: replay the strictly-in-between
+        // heads only, never the current head (the snapshot delivers that).
+        let channel = SubscriberChannel::default();
+        assert!(!manager.replay_gap(&channel, &current, 0).await);
+        assert_eq!(heads(channel.drain().await), vec![1, 2]);
+
+        // `from_head` one below the current head: nothing lies in between.
+        let channel = SubscriberChannel::default();
+        assert!(!manager.replay_gap(&channel, &current, 2).await);
+        assert!(channel.drain().await.is_empty());
+
+        // `from_head` predates the oldest buffered entry: flag a truncated gap.
+        let channel = SubscriberChannel::default();
+        assert!(manager.replay_gap(&channel, &current, -1).await);
+    }
+
+    #[tokio::test]
+    async fn replay_gap_without_buffer_flags_truncation_when_behind() {
+        let manager = IndexerManager::default();
+        let current = contract_updated(5, "0x1");
+
+        let channel = SubscriberChannel::default();
+        assert!(manager.replay_gap(&channel, &current, 2).await);
+        assert!(channel.drain().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn drain_preserves_backlog_then_coalesces_live_values() {
+        let channel = SubscriberChannel::default();
+        let address_a = Felt::from_str("0x1").unwrap();
+        let address_b = Felt::from_str("0x2").unwrap();
+
+        channel.push_ordered(Ok(response(1))).await;
+        channel.push_latest(address_a, Ok(response(2))).await;
+        // Second live value for A coalesces onto the first, keeping only latest.
+        channel.push_latest(address_a, Ok(response(3))).await;
+        channel.push_latest(address_b, Ok(response(4))).await;
+
+        // Ordered backlog first, then one value per contract in arrival order.
+        assert_eq!(heads(channel.drain().await), vec![1, 3, 4]);
+        assert!(channel.drain().await.is_empty());
+    }
+
+    fn response(head: i64) -> SubscribeIndexerResponse {
+        SubscribeIndexerResponse {
+            head,
+            tps: 0,
+            last_block_timestamp: 0,
+            contract_address: vec![],
+            gap_truncated: false,
+            tps_p50: 0.0,
+            tps_p95: 0.0,
+            tps_p99: 0.0,
+            blocks_behind: 0,
+        }
+    }
+
+    #[test]
+    fn tps_percentiles_surface_the_slow_tail() {
+        let mut recorder = LatencyRecorder::new(Duration::from_secs(3600));
+        let update = contract_updated(1, "0x1");
+        let base = Instant::now();
+
+        // Mostly fast 100ms gaps with a few 1s stalls.
+        let mut at = base;
+        for step in 0..100 {
+            let gap = if step % 20 == 0 { 1000 } else { 100 };
+            at += Duration::from_millis(gap);
+            recorder.record(&update, at);
+        }
+
+        // The median tracks the fast path, while the high percentiles report the
+        // low throughput during the stalls.
+        assert!(recorder.tps_at_quantile(0.50) > recorder.tps_at_quantile(0.99));
+    }
+
+    #[tokio::test]
+    async fn publish_updates_respects_wildcard_and_restricted_interest() {
+        let manager = Arc::new(IndexerManager::default());
+        let address_a = Felt::from_str("0x1").unwrap();
+
+        let wildcard = Arc::new(SubscriberChannel::default());
+        let restricted = Arc::new(SubscriberChannel::default());
+        {
+            let mut subs = manager.subscribers.write().await;
+            subs.insert(
+                1,
+                IndexerSubscriber {
+                    contract_addresses: HashSet::new(),
+                    channel: Arc::clone(&wildcard),
+                },
+            );
+            subs.insert(
+                2,
+                IndexerSubscriber {
+                    contract_addresses: HashSet::from([address_a]),
+                    channel: Arc::clone(&restricted),
+                },
+            );
+        }
+
+        Service::publish_updates(Arc::clone(&manager), &contract_updated(1, "0x1"))
+            .await
+            .unwrap();
+        assert_eq!(heads(wildcard.drain().await), vec![1]);
+        assert_eq!(heads(restricted.drain().await), vec![1]);
+
+        // An update for another contract reaches the wildcard but not the
+        // restricted subscriber.
+        Service::publish_updates(Arc::clone(&manager), &contract_updated(2, "0x2"))
+            .await
+            .unwrap();
+        assert_eq!(heads(wildcard.drain().await), vec![2]);
+        assert!(restricted.drain().await.is_empty());
+    }
+}